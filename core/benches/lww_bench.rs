@@ -6,16 +6,13 @@ use synckit_core::document::Document;
 fn bench_single_field_update(c: &mut Criterion) {
     c.bench_function("single_field_update", |b| {
         let mut doc = Document::new("test-doc".to_string());
-        let mut clock = 1u64;
-        
+
         b.iter(|| {
             doc.set_field(
                 black_box("field1".to_string()),
                 black_box(json!("value")),
-                black_box(clock),
                 black_box("client1".to_string()),
             );
-            clock += 1;
         });
     });
 }
@@ -23,48 +20,59 @@ fn bench_single_field_update(c: &mut Criterion) {
 /// Benchmark field retrieval
 fn bench_field_get(c: &mut Criterion) {
     let mut doc = Document::new("test-doc".to_string());
-    doc.set_field("field1".to_string(), json!("value"), 1, "client1".to_string());
-    
+    doc.set_field("field1".to_string(), json!("value"), "client1".to_string());
+
     c.bench_function("field_get", |b| {
         b.iter(|| {
-            black_box(doc.get_field(&"field1".to_string()));
+            black_box(doc.get_field("field1"));
         });
     });
 }
 
-/// Benchmark merge operations with varying field counts
+/// Benchmark merge operations with varying field counts, comparing the
+/// logging-off (`merge_silent`) and logging-on (`merge`, building a
+/// `MergeLog`) paths.
 fn bench_document_merge(c: &mut Criterion) {
     let mut group = c.benchmark_group("document_merge");
-    
+
     for field_count in [10, 50, 100, 500].iter() {
+        // Create two documents with many fields
+        let mut doc1 = Document::new("doc1".to_string());
+        let mut doc2 = Document::new("doc2".to_string());
+
+        // Populate doc1 with fields (older HLC timestamps)
+        for i in 0..*field_count {
+            doc1.set_field(
+                format!("field{}", i),
+                json!(format!("value1_{}", i)),
+                "client1".to_string(),
+            );
+        }
+
+        // Populate doc2 with overlapping fields (newer HLC timestamps)
+        for i in 0..*field_count {
+            doc2.set_field(
+                format!("field{}", i),
+                json!(format!("value2_{}", i)),
+                "client2".to_string(),
+            );
+        }
+
         group.bench_with_input(
-            BenchmarkId::from_parameter(field_count),
+            BenchmarkId::new("silent", field_count),
             field_count,
-            |b, &field_count| {
-                // Create two documents with many fields
-                let mut doc1 = Document::new("doc1".to_string());
-                let mut doc2 = Document::new("doc2".to_string());
-                
-                // Populate doc1 with fields (older timestamps)
-                for i in 0..field_count {
-                    doc1.set_field(
-                        format!("field{}", i),
-                        json!(format!("value1_{}", i)),
-                        1,
-                        "client1".to_string(),
-                    );
-                }
-                
-                // Populate doc2 with overlapping fields (newer timestamps)
-                for i in 0..field_count {
-                    doc2.set_field(
-                        format!("field{}", i),
-                        json!(format!("value2_{}", i)),
-                        2,
-                        "client2".to_string(),
-                    );
-                }
-                
+            |b, _| {
+                b.iter(|| {
+                    let mut doc_copy = doc1.clone();
+                    doc_copy.merge_silent(&doc2);
+                });
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("logged", field_count),
+            field_count,
+            |b, _| {
                 b.iter(|| {
                     let mut doc_copy = doc1.clone();
                     black_box(doc_copy.merge(&doc2));
@@ -91,7 +99,6 @@ fn bench_batch_updates(c: &mut Criterion) {
                         doc.set_field(
                             black_box(format!("field{}", i % 100)), // Reuse some fields
                             black_box(json!(format!("value{}", i))),
-                            black_box(i as u64),
                             black_box("client1".to_string()),
                         );
                     }
@@ -102,38 +109,101 @@ fn bench_batch_updates(c: &mut Criterion) {
     group.finish();
 }
 
-/// Benchmark conflict resolution (same timestamp, different clients)
+/// Benchmark conflict resolution: a clone of `doc1` overwrites the same
+/// field from a second client and is merged back into a copy of `doc1`,
+/// so the merge resolves an LWW conflict by `(hlc, client_id)` order
+/// (the clone's write carries the later HLC and wins).
+/// Compares the logging-off and logging-on merge paths.
 fn bench_conflict_resolution(c: &mut Criterion) {
-    c.bench_function("conflict_resolution", |b| {
-        let mut doc = Document::new("test-doc".to_string());
-        doc.set_field("field1".to_string(), json!("value1"), 1, "client1".to_string());
-        
+    let mut group = c.benchmark_group("conflict_resolution");
+
+    group.bench_function("silent", |b| {
+        let mut doc1 = Document::new("test-doc".to_string());
+        doc1.set_field("field1".to_string(), json!("value1"), "client1".to_string());
+
         b.iter(|| {
-            // Try to set with same timestamp but different client
-            doc.set_field(
+            let mut doc2 = doc1.clone();
+            doc2.set_field(
+                black_box("field1".to_string()),
+                black_box(json!("value2")),
+                black_box("client2".to_string()),
+            );
+            doc1.clone().merge_silent(&doc2);
+        });
+    });
+
+    group.bench_function("logged", |b| {
+        let mut doc1 = Document::new("test-doc".to_string());
+        doc1.set_field("field1".to_string(), json!("value1"), "client1".to_string());
+
+        b.iter(|| {
+            let mut doc2 = doc1.clone();
+            doc2.set_field(
                 black_box("field1".to_string()),
                 black_box(json!("value2")),
-                black_box(1),
                 black_box("client2".to_string()),
             );
+            black_box(doc1.clone().merge(&doc2));
         });
     });
+
+    group.finish();
+}
+
+/// Benchmark merging OR-Set and PN-Counter fields across replicas,
+/// alongside the plain LWW-register merge above.
+fn bench_crdt_merge(c: &mut Criterion) {
+    let mut group = c.benchmark_group("crdt_merge");
+
+    group.bench_function("or_set", |b| {
+        let mut doc1 = Document::new("doc1".to_string());
+        let mut doc2 = Document::new("doc2".to_string());
+        for i in 0..100 {
+            doc1.add_to_set("tags".to_string(), json!(format!("tag{}", i)), "client1".to_string());
+        }
+        for i in 50..150 {
+            doc2.add_to_set("tags".to_string(), json!(format!("tag{}", i)), "client2".to_string());
+        }
+
+        b.iter(|| {
+            let mut doc_copy = doc1.clone();
+            black_box(doc_copy.merge(&doc2));
+        });
+    });
+
+    group.bench_function("pn_counter", |b| {
+        let mut doc1 = Document::new("doc1".to_string());
+        let mut doc2 = Document::new("doc2".to_string());
+        for _ in 0..100 {
+            doc1.increment("likes".to_string(), 1, "client1".to_string());
+        }
+        for _ in 0..100 {
+            doc2.increment("likes".to_string(), 1, "client2".to_string());
+            doc2.decrement("likes".to_string(), 1, "client2".to_string());
+        }
+
+        b.iter(|| {
+            let mut doc_copy = doc1.clone();
+            black_box(doc_copy.merge(&doc2));
+        });
+    });
+
+    group.finish();
 }
 
 /// Benchmark JSON serialization
 fn bench_document_to_json(c: &mut Criterion) {
     let mut doc = Document::new("test-doc".to_string());
-    
+
     // Add 100 fields
     for i in 0..100 {
         doc.set_field(
             format!("field{}", i),
             json!(format!("value{}", i)),
-            1,
             "client1".to_string(),
         );
     }
-    
+
     c.bench_function("document_to_json", |b| {
         b.iter(|| {
             black_box(doc.to_json());
@@ -141,6 +211,132 @@ fn bench_document_to_json(c: &mut Criterion) {
     });
 }
 
+/// Benchmark JSON serialization at larger field counts, asserting that
+/// field order stays stable (insertion order) across repeated runs while
+/// measuring the cost of serializing it.
+fn bench_document_to_json_ordering(c: &mut Criterion) {
+    let mut group = c.benchmark_group("document_to_json_ordering");
+
+    for field_count in [100, 500].iter() {
+        let mut doc = Document::new("test-doc".to_string());
+        let expected_order: Vec<String> = (0..*field_count).map(|i| format!("field{}", i)).collect();
+        for name in &expected_order {
+            doc.set_field(name.clone(), json!(name), "client1".to_string());
+        }
+
+        // Stability check: run once up front rather than inside the
+        // measured loop, since assertions would otherwise be part of the
+        // timed work.
+        let actual_order: Vec<String> = doc
+            .to_json()
+            .as_object()
+            .expect("to_json returns an object")
+            .keys()
+            .cloned()
+            .collect();
+        assert_eq!(actual_order, expected_order, "field order must be stable");
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(field_count),
+            field_count,
+            |b, _| {
+                b.iter(|| {
+                    black_box(doc.to_json());
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Benchmark the parallel merge path against the serial one at field
+/// counts large enough to show the crossover point where splitting
+/// across threads starts paying for itself.
+#[cfg(feature = "rayon")]
+fn bench_parallel_merge(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parallel_merge");
+
+    for field_count in [500, 5_000, 50_000].iter() {
+        let mut doc1 = Document::new("doc1".to_string());
+        let mut doc2 = Document::new("doc2".to_string());
+        for i in 0..*field_count {
+            doc1.set_field(
+                format!("field{}", i),
+                json!(format!("value1_{}", i)),
+                "client1".to_string(),
+            );
+        }
+        for i in 0..*field_count {
+            doc2.set_field(
+                format!("field{}", i),
+                json!(format!("value2_{}", i)),
+                "client2".to_string(),
+            );
+        }
+
+        group.bench_with_input(
+            BenchmarkId::new("serial", field_count),
+            field_count,
+            |b, _| {
+                b.iter(|| {
+                    let mut doc_copy = doc1.clone();
+                    doc_copy.merge_silent(&doc2);
+                });
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("parallel", field_count),
+            field_count,
+            |b, _| {
+                b.iter(|| {
+                    let mut doc_copy = doc1.clone();
+                    doc_copy.merge_parallel_silent(&doc2);
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Benchmark `set_field` and `merge` with the `metrics` feature enabled,
+/// to confirm the counters stay negligible overhead next to the baseline
+/// `single_field_update` and `document_merge` benchmarks above.
+#[cfg(feature = "metrics")]
+fn bench_metrics_overhead(c: &mut Criterion) {
+    let mut group = c.benchmark_group("metrics_overhead");
+
+    group.bench_function("single_field_update", |b| {
+        let mut doc = Document::new("test-doc".to_string());
+
+        b.iter(|| {
+            doc.set_field(
+                black_box("field1".to_string()),
+                black_box(json!("value")),
+                black_box("client1".to_string()),
+            );
+        });
+    });
+
+    group.bench_function("document_merge_100", |b| {
+        let mut doc1 = Document::new("doc1".to_string());
+        let mut doc2 = Document::new("doc2".to_string());
+        for i in 0..100 {
+            doc1.set_field(format!("field{}", i), json!(format!("value1_{}", i)), "client1".to_string());
+        }
+        for i in 0..100 {
+            doc2.set_field(format!("field{}", i), json!(format!("value2_{}", i)), "client2".to_string());
+        }
+
+        b.iter(|| {
+            let mut doc_copy = doc1.clone();
+            doc_copy.merge_silent(&doc2);
+        });
+    });
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_single_field_update,
@@ -148,6 +344,22 @@ criterion_group!(
     bench_document_merge,
     bench_batch_updates,
     bench_conflict_resolution,
+    bench_crdt_merge,
     bench_document_to_json,
+    bench_document_to_json_ordering,
 );
+
+#[cfg(feature = "rayon")]
+criterion_group!(parallel_benches, bench_parallel_merge);
+
+#[cfg(feature = "metrics")]
+criterion_group!(metrics_benches, bench_metrics_overhead);
+
+#[cfg(all(feature = "rayon", feature = "metrics"))]
+criterion_main!(benches, parallel_benches, metrics_benches);
+#[cfg(all(feature = "rayon", not(feature = "metrics")))]
+criterion_main!(benches, parallel_benches);
+#[cfg(all(not(feature = "rayon"), feature = "metrics"))]
+criterion_main!(benches, metrics_benches);
+#[cfg(all(not(feature = "rayon"), not(feature = "metrics")))]
 criterion_main!(benches);
@@ -0,0 +1,179 @@
+//! CRDT value types usable as `Document` field values, beyond the default
+//! last-write-wins register: an observed-remove set and a PN-counter.
+
+use crate::hlc::Hlc;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// A tag identifying one `add` of an element into an [`OrSet`]: the client
+/// that added it and the HLC of that write. Tags are unique per add, which
+/// is what lets concurrent adds of the same value coexist until a removal
+/// that has observed both is merged in.
+pub type OrSetTag = (String, Hlc);
+
+/// An observed-remove set: an element is present iff it has at least one
+/// add-tag that is not also in the removed-tag set. Concurrent adds win
+/// over a concurrent remove that never observed them, which is the
+/// "add-wins" behavior expected of an OR-Set.
+///
+/// Backed by `BTreeMap`/`BTreeSet` rather than `HashMap`/`HashSet`, ordered
+/// on `OrSetTag` (which is itself ordered on `(client_id, hlc)`), so that
+/// [`values`](Self::values) iterates deterministically — matching the
+/// `IndexMap`-backed `Document::fields` map, this keeps a replica's
+/// `to_json` output stable across runs rather than varying with
+/// `HashMap`'s randomized iteration order.
+#[derive(Debug, Clone, Default)]
+pub struct OrSet {
+    adds: BTreeMap<OrSetTag, Value>,
+    removes: BTreeSet<OrSetTag>,
+}
+
+impl OrSet {
+    /// Record an add of `value` under a fresh, unique `tag`.
+    pub fn add(&mut self, tag: OrSetTag, value: Value) {
+        self.adds.insert(tag, value);
+    }
+
+    /// Remove every add-tag currently observed for `value`. A concurrent
+    /// add of the same value that this replica has not yet seen will
+    /// still survive, since its tag isn't in `removes` yet.
+    pub fn remove(&mut self, value: &Value) {
+        let observed: Vec<OrSetTag> = self
+            .adds
+            .iter()
+            .filter(|(_, v)| *v == value)
+            .map(|(tag, _)| tag.clone())
+            .collect();
+        self.removes.extend(observed);
+    }
+
+    /// The set's current elements: the value of every add-tag not covered
+    /// by a remove, each distinct value yielded once, in tag order.
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        let mut seen: Vec<&Value> = Vec::new();
+        self.adds
+            .iter()
+            .filter(|(tag, _)| !self.removes.contains(tag))
+            .map(|(_, value)| value)
+            .filter(move |value| {
+                if seen.contains(value) {
+                    false
+                } else {
+                    seen.push(*value);
+                    true
+                }
+            })
+    }
+
+    /// Merge another replica's OR-Set state into this one: adds union,
+    /// removes union. Commutative, associative, and idempotent.
+    pub fn merge(&mut self, other: &OrSet) {
+        for (tag, value) in &other.adds {
+            self.adds.entry(tag.clone()).or_insert_with(|| value.clone());
+        }
+        self.removes.extend(other.removes.iter().cloned());
+    }
+}
+
+/// A PN-counter: a counter that supports both increment and decrement and
+/// merges without losing concurrent updates, by tracking per-replica
+/// increment/decrement totals separately.
+#[derive(Debug, Clone, Default)]
+pub struct PnCounter {
+    increments: HashMap<String, i64>,
+    decrements: HashMap<String, i64>,
+}
+
+impl PnCounter {
+    /// The counter's current value: `sum(increments) - sum(decrements)`.
+    pub fn value(&self) -> i64 {
+        self.increments.values().sum::<i64>() - self.decrements.values().sum::<i64>()
+    }
+
+    /// Add `amount` to `client_id`'s running increment total.
+    pub fn increment(&mut self, client_id: &str, amount: i64) {
+        *self.increments.entry(client_id.to_string()).or_insert(0) += amount;
+    }
+
+    /// Add `amount` to `client_id`'s running decrement total.
+    pub fn decrement(&mut self, client_id: &str, amount: i64) {
+        *self.decrements.entry(client_id.to_string()).or_insert(0) += amount;
+    }
+
+    /// Merge another replica's counter state in by taking the element-wise
+    /// max of each client's increment and decrement totals. Safe because a
+    /// replica's own total only ever grows.
+    pub fn merge(&mut self, other: &PnCounter) {
+        for (client_id, &amount) in &other.increments {
+            let entry = self.increments.entry(client_id.clone()).or_insert(0);
+            *entry = (*entry).max(amount);
+        }
+        for (client_id, &amount) in &other.decrements {
+            let entry = self.decrements.entry(client_id.clone()).or_insert(0);
+            *entry = (*entry).max(amount);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn or_set_concurrent_add_beats_remove_that_did_not_observe_it() {
+        let mut replica_a = OrSet::default();
+        replica_a.add(("a".to_string(), Hlc { wall: 1, counter: 0 }), json!("x"));
+
+        let mut replica_b = OrSet::default();
+        replica_b.add(("b".to_string(), Hlc { wall: 2, counter: 0 }), json!("x"));
+        replica_b.remove(&json!("x"));
+
+        let mut merged = replica_a.clone();
+        merged.merge(&replica_b);
+
+        assert_eq!(merged.values().collect::<Vec<_>>(), vec![&json!("x")]);
+    }
+
+    #[test]
+    fn or_set_values_are_ordered_by_tag_regardless_of_add_order() {
+        let mut forward = OrSet::default();
+        forward.add(("a".to_string(), Hlc { wall: 1, counter: 0 }), json!("first"));
+        forward.add(("b".to_string(), Hlc { wall: 2, counter: 0 }), json!("second"));
+
+        let mut backward = OrSet::default();
+        backward.add(("b".to_string(), Hlc { wall: 2, counter: 0 }), json!("second"));
+        backward.add(("a".to_string(), Hlc { wall: 1, counter: 0 }), json!("first"));
+
+        assert_eq!(
+            forward.values().collect::<Vec<_>>(),
+            backward.values().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn or_set_remove_after_merge_clears_observed_adds() {
+        let mut replica_a = OrSet::default();
+        replica_a.add(("a".to_string(), Hlc { wall: 1, counter: 0 }), json!("x"));
+
+        let mut replica_b = replica_a.clone();
+        replica_b.remove(&json!("x"));
+
+        let mut merged = replica_a;
+        merged.merge(&replica_b);
+        assert_eq!(merged.values().count(), 0);
+    }
+
+    #[test]
+    fn pn_counter_merge_takes_max_per_client() {
+        let mut replica_a = PnCounter::default();
+        replica_a.increment("client1", 3);
+
+        let mut replica_b = PnCounter::default();
+        replica_b.increment("client1", 5);
+        replica_b.decrement("client2", 2);
+
+        replica_a.merge(&replica_b);
+        assert_eq!(replica_a.value(), 5 - 2);
+    }
+}
@@ -0,0 +1,920 @@
+//! CRDT document model: a map of named fields that merges deterministically
+//! across replicas.
+//!
+//! Requires the `serde_json/preserve_order` feature so that `Value::Object`
+//! keeps insertion order rather than sorting keys, matching the `IndexMap`
+//! field map below.
+
+use crate::crdt::{OrSet, PnCounter};
+use crate::history::{HistoryEntry, HistoryRing};
+use crate::hlc::Hlc;
+use crate::merge_log::{FieldMergeResult, LoserInfo, MergeLog};
+#[cfg(feature = "metrics")]
+use crate::metrics::{DocumentMetrics, MetricsSnapshot};
+use indexmap::IndexMap;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// The CRDT type stored under a field name. `LwwRegister` is the default,
+/// last-write-wins behavior; `OrSet` and `PnCounter` merge without losing
+/// concurrent updates.
+#[derive(Debug, Clone)]
+pub enum FieldValue {
+    LwwRegister(Value),
+    OrSet(OrSet),
+    PnCounter(PnCounter),
+}
+
+impl FieldValue {
+    /// Materialize the field's current value as JSON, for `get_field` and
+    /// `to_json`.
+    fn to_json(&self) -> Value {
+        match self {
+            FieldValue::LwwRegister(value) => value.clone(),
+            FieldValue::OrSet(set) => Value::Array(set.values().cloned().collect()),
+            FieldValue::PnCounter(counter) => json!(counter.value()),
+        }
+    }
+}
+
+/// A single field's value, the HLC timestamp of the write that produced
+/// it, and the client that made that write.
+#[derive(Debug, Clone)]
+struct FieldEntry {
+    value: FieldValue,
+    hlc: Hlc,
+    client_id: String,
+}
+
+impl FieldEntry {
+    /// Ordering key used to resolve concurrent LWW writes: HLC first, then
+    /// `client_id` as a last-resort tiebreak for the (practically
+    /// impossible) case of two clients producing the exact same HLC.
+    fn order_key(&self) -> (Hlc, &str) {
+        (self.hlc, self.client_id.as_str())
+    }
+}
+
+/// Outcome of merging a single field, used to decide whether to record
+/// history and how to describe the change in a `MergeLog`.
+enum FieldMergeOutcome {
+    Unchanged,
+    Replaced { loser: FieldEntry },
+    CrdtMerged,
+    /// The field did not exist locally before the merge and was adopted
+    /// as-is from the remote side. Only produced by [`merge_parallel`]'s
+    /// chunked path; the serial path handles a missing local field
+    /// directly in `merge_into` instead of going through `merge_field`.
+    #[cfg(feature = "rayon")]
+    Inserted,
+}
+
+/// One field's merge outcome from [`Document::merge_parallel`]'s chunked
+/// path, carried out of the `rayon` fold as plain data and applied to
+/// `self` back on the calling thread.
+#[cfg(feature = "rayon")]
+struct ChunkMergeResult {
+    name: String,
+    merged: FieldEntry,
+    outcome: FieldMergeOutcome,
+    remote_hlc: Hlc,
+    type_mismatch: bool,
+    same_hlc_diff_client: bool,
+}
+
+/// A CRDT document: a named collection of fields that can be merged
+/// commutatively and idempotently with another replica's view.
+///
+/// Fields are kept in an insertion-order-preserving map rather than a
+/// `HashMap`, so that two replicas which applied the same operations in
+/// the same causal order produce byte-identical `to_json` output — this
+/// matters for snapshots, diffs, and content hashing across replicas.
+#[derive(Debug, Clone)]
+pub struct Document {
+    id: String,
+    fields: IndexMap<String, FieldEntry>,
+    clock: Hlc,
+    history_depth: usize,
+    history: HashMap<String, HistoryRing>,
+    #[cfg(feature = "metrics")]
+    metrics: DocumentMetrics,
+}
+
+impl Document {
+    /// Create a new, empty document for the given id. Per-field history is
+    /// disabled by default; use [`with_history_depth`](Self::with_history_depth)
+    /// to enable it.
+    pub fn new(id: String) -> Self {
+        Self {
+            id,
+            fields: IndexMap::new(),
+            clock: Hlc::ZERO,
+            history_depth: 0,
+            history: HashMap::new(),
+            #[cfg(feature = "metrics")]
+            metrics: DocumentMetrics::default(),
+        }
+    }
+
+    /// Enable a bounded per-field version history, keeping up to `depth`
+    /// prior versions of each field for conflict provenance or undo.
+    pub fn with_history_depth(mut self, depth: usize) -> Self {
+        self.history_depth = depth;
+        self
+    }
+
+    /// The document's id.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Prior versions of `field`, oldest first, up to this document's
+    /// configured history depth. Empty if history is disabled or the
+    /// field has never been overwritten.
+    pub fn history_for(&self, field: &str) -> impl Iterator<Item = &HistoryEntry> {
+        self.history.get(field).into_iter().flat_map(|ring| ring.iter())
+    }
+
+    fn record_history(&mut self, field: &str, replaced: &FieldEntry) {
+        if self.history_depth == 0 {
+            return;
+        }
+        self.history
+            .entry(field.to_string())
+            .or_insert_with(|| HistoryRing::new(self.history_depth))
+            .push(HistoryEntry {
+                value: replaced.value.to_json(),
+                hlc: replaced.hlc,
+                client_id: replaced.client_id.clone(),
+            });
+    }
+
+    /// If `field` already holds a value that doesn't satisfy `is_wanted_type`
+    /// (e.g. an `add_to_set` call landing on a field last written as an LWW
+    /// register), return a clone of its current entry so the caller can
+    /// record it to history before clobbering it with a fresh default.
+    fn type_mismatch_history(
+        &self,
+        field: &str,
+        is_wanted_type: impl Fn(&FieldValue) -> bool,
+    ) -> Option<FieldEntry> {
+        self.fields
+            .get(field)
+            .filter(|entry| !is_wanted_type(&entry.value))
+            .cloned()
+    }
+
+    /// Set a field locally as an LWW register. The write is timestamped
+    /// with this replica's own HLC, ticked forward from its previous
+    /// value, so repeated local writes are always causally ordered after
+    /// one another.
+    pub fn set_field(&mut self, field: String, value: Value, client_id: String) {
+        self.clock = Hlc::tick_local(self.clock);
+        let previous = self.fields.insert(
+            field.clone(),
+            FieldEntry {
+                value: FieldValue::LwwRegister(value),
+                hlc: self.clock,
+                client_id,
+            },
+        );
+        if let Some(previous) = previous {
+            self.record_history(&field, &previous);
+        }
+        #[cfg(feature = "metrics")]
+        self.metrics.record_field_set();
+    }
+
+    /// Add `value` to an OR-Set field, creating it if absent. Coexists
+    /// with concurrent adds and wins over a concurrent remove that has
+    /// not observed this add.
+    pub fn add_to_set(&mut self, field: String, value: Value, client_id: String) {
+        self.clock = Hlc::tick_local(self.clock);
+        let clock = self.clock;
+        let tag = (client_id.clone(), clock);
+        let displaced = self.type_mismatch_history(&field, |value| matches!(value, FieldValue::OrSet(_)));
+        if let Some(displaced) = &displaced {
+            self.record_history(&field, displaced);
+        }
+        let entry = self.fields.entry(field).or_insert_with(|| FieldEntry {
+            value: FieldValue::OrSet(OrSet::default()),
+            hlc: clock,
+            client_id: client_id.clone(),
+        });
+        if displaced.is_some() {
+            entry.value = FieldValue::OrSet(OrSet::default());
+        }
+        if let FieldValue::OrSet(set) = &mut entry.value {
+            set.add(tag, value);
+        }
+        entry.hlc = clock;
+        entry.client_id = client_id;
+        #[cfg(feature = "metrics")]
+        self.metrics.record_field_set();
+    }
+
+    /// Remove `value` from an OR-Set field, if present. Only the adds this
+    /// replica has already observed are removed; a concurrent add of the
+    /// same value this replica hasn't seen yet survives.
+    pub fn remove_from_set(&mut self, field: &str, value: &Value, client_id: String) {
+        self.clock = Hlc::tick_local(self.clock);
+        let clock = self.clock;
+        if let Some(entry) = self.fields.get_mut(field) {
+            if let FieldValue::OrSet(set) = &mut entry.value {
+                set.remove(value);
+                entry.hlc = clock;
+                entry.client_id = client_id;
+            }
+        }
+        #[cfg(feature = "metrics")]
+        self.metrics.record_field_set();
+    }
+
+    /// Add `amount` to a PN-Counter field, creating it if absent.
+    pub fn increment(&mut self, field: String, amount: i64, client_id: String) {
+        self.clock = Hlc::tick_local(self.clock);
+        let clock = self.clock;
+        let displaced = self.type_mismatch_history(&field, |value| matches!(value, FieldValue::PnCounter(_)));
+        if let Some(displaced) = &displaced {
+            self.record_history(&field, displaced);
+        }
+        let entry = self.fields.entry(field).or_insert_with(|| FieldEntry {
+            value: FieldValue::PnCounter(PnCounter::default()),
+            hlc: clock,
+            client_id: client_id.clone(),
+        });
+        if displaced.is_some() {
+            entry.value = FieldValue::PnCounter(PnCounter::default());
+        }
+        if let FieldValue::PnCounter(counter) = &mut entry.value {
+            counter.increment(&client_id, amount);
+        }
+        entry.hlc = clock;
+        entry.client_id = client_id;
+        #[cfg(feature = "metrics")]
+        self.metrics.record_field_set();
+    }
+
+    /// Subtract `amount` from a PN-Counter field, creating it if absent.
+    pub fn decrement(&mut self, field: String, amount: i64, client_id: String) {
+        self.clock = Hlc::tick_local(self.clock);
+        let clock = self.clock;
+        let displaced = self.type_mismatch_history(&field, |value| matches!(value, FieldValue::PnCounter(_)));
+        if let Some(displaced) = &displaced {
+            self.record_history(&field, displaced);
+        }
+        let entry = self.fields.entry(field).or_insert_with(|| FieldEntry {
+            value: FieldValue::PnCounter(PnCounter::default()),
+            hlc: clock,
+            client_id: client_id.clone(),
+        });
+        if displaced.is_some() {
+            entry.value = FieldValue::PnCounter(PnCounter::default());
+        }
+        if let FieldValue::PnCounter(counter) = &mut entry.value {
+            counter.decrement(&client_id, amount);
+        }
+        entry.hlc = clock;
+        entry.client_id = client_id;
+        #[cfg(feature = "metrics")]
+        self.metrics.record_field_set();
+    }
+
+    /// A snapshot of this document's operation counters, suitable for
+    /// scraping into a time-series store. Only available when the
+    /// `metrics` feature is enabled.
+    #[cfg(feature = "metrics")]
+    pub fn metrics_snapshot(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// Read a field's current value, if set.
+    pub fn get_field(&self, field: &str) -> Option<Value> {
+        self.fields.get(field).map(|entry| entry.value.to_json())
+    }
+
+    /// Iterate over all fields in stable insertion order.
+    pub fn iter_fields(&self) -> impl Iterator<Item = (&str, Value)> {
+        self.fields
+            .iter()
+            .map(|(name, entry)| (name.as_str(), entry.value.to_json()))
+    }
+
+    /// Merge another document's fields into this one, returning a
+    /// [`MergeLog`] describing which side won each field and any
+    /// anomalies observed. Fields present on both sides merge according
+    /// to their CRDT type (LWW picks the greater `(hlc, client_id)`,
+    /// OR-Set unions adds/removes, PN-Counter takes the element-wise
+    /// max); a field present only on `other` is adopted as-is. This
+    /// replica's clock is advanced past each remote timestamp it
+    /// observes so future local writes stay causally ahead of everything
+    /// merged in. `merge` is commutative and idempotent.
+    pub fn merge(&mut self, other: &Document) -> MergeLog {
+        let mut log = MergeLog::default();
+        self.merge_into(other, Some(&mut log));
+        log
+    }
+
+    /// Merge like [`merge`](Self::merge), but without building a
+    /// [`MergeLog`], for the hot path where callers don't need an audit
+    /// trail. History recording (if enabled) still happens.
+    pub fn merge_silent(&mut self, other: &Document) {
+        self.merge_into(other, None);
+    }
+
+    fn merge_into(&mut self, other: &Document, mut log: Option<&mut MergeLog>) {
+        #[cfg(feature = "metrics")]
+        let merge_start = std::time::Instant::now();
+
+        for (name, remote) in &other.fields {
+            self.clock = Hlc::tick_remote(self.clock, remote.hlc);
+            match self.fields.get_mut(name) {
+                Some(local) => {
+                    if let Some(log) = log.as_deref_mut() {
+                        if std::mem::discriminant(&local.value) != std::mem::discriminant(&remote.value) {
+                            log.warnings.push(format!(
+                                "field `{name}` merged across mismatched CRDT types; falling back to LWW"
+                            ));
+                        }
+                        if local.hlc == remote.hlc && local.client_id != remote.client_id {
+                            log.warnings.push(format!(
+                                "field `{name}` had identical HLC timestamps from clients `{}` and `{}`",
+                                local.client_id, remote.client_id
+                            ));
+                        }
+                    }
+                    match Self::merge_field(local, remote) {
+                        FieldMergeOutcome::Replaced { loser } => {
+                            self.record_history(name, &loser);
+                            #[cfg(feature = "metrics")]
+                            {
+                                self.metrics.record_conflict_winner(&remote.client_id);
+                                self.metrics.record_conflict_loser(&loser.client_id);
+                            }
+                            if let Some(log) = log.as_deref_mut() {
+                                log.changes.push(FieldMergeResult {
+                                    field: name.clone(),
+                                    winner_client: remote.client_id.clone(),
+                                    loser: Some(LoserInfo {
+                                        value: loser.value.to_json(),
+                                        hlc: loser.hlc,
+                                        client_id: loser.client_id,
+                                    }),
+                                });
+                            }
+                        }
+                        FieldMergeOutcome::CrdtMerged => {
+                            if let Some(log) = log.as_deref_mut() {
+                                log.changes.push(FieldMergeResult {
+                                    field: name.clone(),
+                                    winner_client: remote.client_id.clone(),
+                                    loser: None,
+                                });
+                            }
+                        }
+                        FieldMergeOutcome::Unchanged => {}
+                        #[cfg(feature = "rayon")]
+                        FieldMergeOutcome::Inserted => unreachable!(
+                            "merge_field only runs when a local entry already exists"
+                        ),
+                    }
+                }
+                None => {
+                    self.fields.insert(name.clone(), remote.clone());
+                    if let Some(log) = log.as_deref_mut() {
+                        log.warnings.push(format!(
+                            "field `{name}` did not exist locally before merge; adopted from remote"
+                        ));
+                        log.changes.push(FieldMergeResult {
+                            field: name.clone(),
+                            winner_client: remote.client_id.clone(),
+                            loser: None,
+                        });
+                    }
+                }
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        self.metrics.record_merge(merge_start.elapsed());
+    }
+
+    fn merge_field(local: &mut FieldEntry, remote: &FieldEntry) -> FieldMergeOutcome {
+        let (merged, outcome) = Self::merge_field_pure(local, remote);
+        *local = merged;
+        outcome
+    }
+
+    /// Side-effect-free version of field merging: given a local and a
+    /// remote entry, compute the merged entry and what happened, without
+    /// mutating either input. This is what lets [`merge_parallel`]
+    /// (behind the `rayon` feature) compute merges for many fields
+    /// concurrently and fold the results in afterwards.
+    fn merge_field_pure(local: &FieldEntry, remote: &FieldEntry) -> (FieldEntry, FieldMergeOutcome) {
+        match (&local.value, &remote.value) {
+            (FieldValue::OrSet(local_set), FieldValue::OrSet(remote_set)) => {
+                let mut merged_set = local_set.clone();
+                merged_set.merge(remote_set);
+                let (hlc, client_id) = Self::newer_origin(local, remote);
+                let merged = FieldEntry {
+                    value: FieldValue::OrSet(merged_set),
+                    hlc,
+                    client_id,
+                };
+                (merged, FieldMergeOutcome::CrdtMerged)
+            }
+            (FieldValue::PnCounter(local_counter), FieldValue::PnCounter(remote_counter)) => {
+                let mut merged_counter = local_counter.clone();
+                merged_counter.merge(remote_counter);
+                let (hlc, client_id) = Self::newer_origin(local, remote);
+                let merged = FieldEntry {
+                    value: FieldValue::PnCounter(merged_counter),
+                    hlc,
+                    client_id,
+                };
+                (merged, FieldMergeOutcome::CrdtMerged)
+            }
+            // Same-type LWW registers, or mismatched CRDT types for the
+            // same field name (a rare client bug): fall back to LWW.
+            _ => {
+                if remote.order_key() > local.order_key() {
+                    (remote.clone(), FieldMergeOutcome::Replaced { loser: local.clone() })
+                } else {
+                    (local.clone(), FieldMergeOutcome::Unchanged)
+                }
+            }
+        }
+    }
+
+    /// The `(hlc, client_id)` of whichever side observed the field more
+    /// recently, used to attribute a CRDT-merged entry's provenance.
+    fn newer_origin(local: &FieldEntry, remote: &FieldEntry) -> (Hlc, String) {
+        if remote.hlc > local.hlc {
+            (remote.hlc, remote.client_id.clone())
+        } else {
+            (local.hlc, local.client_id.clone())
+        }
+    }
+
+    /// Merge another document's fields into this one using multiple
+    /// threads, returning a [`MergeLog`] like [`merge`](Self::merge)
+    /// does. Each field's CRDT merge is side-effect-free and
+    /// order-independent, so fields are partitioned into chunks and
+    /// merged against this document's current state concurrently via
+    /// `merge_field_pure`; the per-chunk results are then folded into
+    /// `self` on this thread, preserving the same commutativity and
+    /// idempotence as the serial `merge`.
+    ///
+    /// The chunk size is chosen adaptively from the number of fields
+    /// being merged and the available thread count, so small documents
+    /// stay on the serial path (where the parallel bookkeeping wouldn't
+    /// pay for itself) and large ones spread across cores without
+    /// oversubscribing them.
+    #[cfg(feature = "rayon")]
+    pub fn merge_parallel(&mut self, other: &Document) -> MergeLog {
+        self.merge_parallel_into(other, true).unwrap_or_default()
+    }
+
+    /// Like [`merge_parallel`](Self::merge_parallel), but without
+    /// building a [`MergeLog`].
+    #[cfg(feature = "rayon")]
+    pub fn merge_parallel_silent(&mut self, other: &Document) {
+        self.merge_parallel_into(other, false);
+    }
+
+    #[cfg(feature = "rayon")]
+    fn merge_parallel_into(&mut self, other: &Document, log_enabled: bool) -> Option<MergeLog> {
+        use rayon::prelude::*;
+
+        #[cfg(feature = "metrics")]
+        let merge_start = std::time::Instant::now();
+
+        /// Fan-out factor: target roughly this many chunks per thread,
+        /// so work stays balanced even when some chunks finish faster
+        /// than others.
+        const FAN_OUT: usize = 4;
+
+        let remote_fields: Vec<(&String, &FieldEntry)> = other.fields.iter().collect();
+        let total_fields = remote_fields.len();
+        if total_fields == 0 {
+            return log_enabled.then(MergeLog::default);
+        }
+
+        let threads = rayon::current_num_threads().max(1);
+        let serial_threshold = threads * FAN_OUT;
+
+        if total_fields <= serial_threshold {
+            // Not enough work to amortize splitting across threads.
+            return if log_enabled {
+                Some(self.merge(other))
+            } else {
+                self.merge_silent(other);
+                None
+            };
+        }
+
+        let chunk_size = total_fields.div_ceil(threads * FAN_OUT).max(1);
+        let local_fields = &self.fields;
+        let chunk_results: Vec<Vec<ChunkMergeResult>> = remote_fields
+            .par_chunks(chunk_size)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .map(|(name, remote)| match local_fields.get(name.as_str()) {
+                        Some(local) => {
+                            let type_mismatch = std::mem::discriminant(&local.value)
+                                != std::mem::discriminant(&remote.value);
+                            let same_hlc_diff_client =
+                                local.hlc == remote.hlc && local.client_id != remote.client_id;
+                            let (merged, outcome) = Self::merge_field_pure(local, remote);
+                            ChunkMergeResult {
+                                name: (*name).clone(),
+                                merged,
+                                outcome,
+                                remote_hlc: remote.hlc,
+                                type_mismatch,
+                                same_hlc_diff_client,
+                            }
+                        }
+                        None => ChunkMergeResult {
+                            name: (*name).clone(),
+                            merged: (*remote).clone(),
+                            outcome: FieldMergeOutcome::Inserted,
+                            remote_hlc: remote.hlc,
+                            type_mismatch: false,
+                            same_hlc_diff_client: false,
+                        },
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut log = log_enabled.then(MergeLog::default);
+        for chunk in chunk_results {
+            for result in chunk {
+                let ChunkMergeResult {
+                    name,
+                    merged,
+                    outcome,
+                    remote_hlc,
+                    type_mismatch,
+                    same_hlc_diff_client,
+                } = result;
+                self.clock = Hlc::tick_remote(self.clock, remote_hlc);
+                if let Some(log) = log.as_mut() {
+                    if type_mismatch {
+                        log.warnings.push(format!(
+                            "field `{name}` merged across mismatched CRDT types; falling back to LWW"
+                        ));
+                    }
+                    if same_hlc_diff_client {
+                        log.warnings.push(format!(
+                            "field `{name}` had identical HLC timestamps from different clients"
+                        ));
+                    }
+                }
+                match &outcome {
+                    FieldMergeOutcome::Replaced { loser } => {
+                        self.record_history(&name, loser);
+                        #[cfg(feature = "metrics")]
+                        {
+                            self.metrics.record_conflict_winner(&merged.client_id);
+                            self.metrics.record_conflict_loser(&loser.client_id);
+                        }
+                        if let Some(log) = log.as_mut() {
+                            log.changes.push(FieldMergeResult {
+                                field: name.clone(),
+                                winner_client: merged.client_id.clone(),
+                                loser: Some(LoserInfo {
+                                    value: loser.value.to_json(),
+                                    hlc: loser.hlc,
+                                    client_id: loser.client_id.clone(),
+                                }),
+                            });
+                        }
+                    }
+                    FieldMergeOutcome::CrdtMerged => {
+                        if let Some(log) = log.as_mut() {
+                            log.changes.push(FieldMergeResult {
+                                field: name.clone(),
+                                winner_client: other.fields[name.as_str()].client_id.clone(),
+                                loser: None,
+                            });
+                        }
+                    }
+                    FieldMergeOutcome::Inserted => {
+                        if let Some(log) = log.as_mut() {
+                            log.warnings.push(format!(
+                                "field `{name}` did not exist locally before merge; adopted from remote"
+                            ));
+                            log.changes.push(FieldMergeResult {
+                                field: name.clone(),
+                                winner_client: merged.client_id.clone(),
+                                loser: None,
+                            });
+                        }
+                    }
+                    FieldMergeOutcome::Unchanged => {}
+                }
+                self.fields.insert(name, merged);
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        self.metrics.record_merge(merge_start.elapsed());
+
+        log
+    }
+
+    /// Serialize the document's current field values to a JSON object,
+    /// preserving field insertion order so replicas that applied the same
+    /// operations in the same causal order serialize byte-identically.
+    pub fn to_json(&self) -> Value {
+        let map: serde_json::Map<String, Value> = self
+            .fields
+            .iter()
+            .map(|(name, entry)| (name.clone(), entry.value.to_json()))
+            .collect();
+        #[cfg(feature = "metrics")]
+        {
+            let bytes = serde_json::to_string(&map).map(|s| s.len() as u64).unwrap_or(0);
+            self.metrics.record_bytes_serialized(bytes);
+        }
+        Value::Object(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn later_local_write_wins() {
+        let mut doc = Document::new("doc".to_string());
+        doc.set_field("a".to_string(), json!(1), "client1".to_string());
+        doc.set_field("a".to_string(), json!(2), "client1".to_string());
+        assert_eq!(doc.get_field("a"), Some(json!(2)));
+    }
+
+    #[test]
+    fn merge_picks_the_later_hlc() {
+        let mut doc1 = Document::new("doc1".to_string());
+        let mut doc2 = Document::new("doc2".to_string());
+        doc1.set_field("a".to_string(), json!("old"), "client1".to_string());
+        doc2.set_field("a".to_string(), json!("new"), "client2".to_string());
+
+        doc1.merge(&doc2);
+        assert_eq!(doc1.get_field("a"), Some(json!("new")));
+    }
+
+    #[test]
+    fn merge_is_idempotent() {
+        let mut doc1 = Document::new("doc1".to_string());
+        let mut doc2 = Document::new("doc2".to_string());
+        doc1.set_field("a".to_string(), json!("old"), "client1".to_string());
+        doc2.set_field("a".to_string(), json!("new"), "client2".to_string());
+
+        doc1.merge(&doc2);
+        doc1.merge(&doc2);
+        assert_eq!(doc1.get_field("a"), Some(json!("new")));
+    }
+
+    #[test]
+    fn merge_is_commutative() {
+        let mut doc1 = Document::new("doc1".to_string());
+        let mut doc2 = Document::new("doc2".to_string());
+        doc1.set_field("a".to_string(), json!("from1"), "client1".to_string());
+        doc2.set_field("a".to_string(), json!("from2"), "client2".to_string());
+
+        let mut merged_1_then_2 = doc1.clone();
+        merged_1_then_2.merge(&doc2);
+
+        let mut merged_2_then_1 = doc2.clone();
+        merged_2_then_1.merge(&doc1);
+
+        assert_eq!(
+            merged_1_then_2.get_field("a"),
+            merged_2_then_1.get_field("a")
+        );
+    }
+
+    #[test]
+    fn or_set_keeps_concurrent_adds_across_replicas() {
+        let mut doc1 = Document::new("doc1".to_string());
+        let mut doc2 = Document::new("doc2".to_string());
+        doc1.add_to_set("tags".to_string(), json!("a"), "client1".to_string());
+        doc2.add_to_set("tags".to_string(), json!("b"), "client2".to_string());
+
+        doc1.merge(&doc2);
+        let mut tags: Vec<Value> = doc1
+            .get_field("tags")
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .clone();
+        tags.sort_by_key(|v| v.to_string());
+        assert_eq!(tags, vec![json!("a"), json!("b")]);
+    }
+
+    #[test]
+    fn pn_counter_merges_across_replicas_without_losing_updates() {
+        let mut doc1 = Document::new("doc1".to_string());
+        let mut doc2 = Document::new("doc2".to_string());
+        doc1.increment("likes".to_string(), 2, "client1".to_string());
+        doc2.increment("likes".to_string(), 5, "client2".to_string());
+        doc2.decrement("likes".to_string(), 1, "client2".to_string());
+
+        doc1.merge(&doc2);
+        assert_eq!(doc1.get_field("likes"), Some(json!(2 + 5 - 1)));
+    }
+
+    #[test]
+    fn merge_log_reports_winner_and_loser_for_lww_conflict() {
+        let mut doc1 = Document::new("doc1".to_string());
+        let mut doc2 = Document::new("doc2".to_string());
+        doc1.set_field("a".to_string(), json!("old"), "client1".to_string());
+        doc2.set_field("a".to_string(), json!("new"), "client2".to_string());
+
+        let log = doc1.merge(&doc2);
+        assert_eq!(log.changes.len(), 1);
+        assert_eq!(log.changes[0].winner_client, "client2");
+        assert_eq!(log.changes[0].loser.as_ref().unwrap().value, json!("old"));
+    }
+
+    #[test]
+    fn merge_log_warns_on_field_missing_locally() {
+        let mut doc1 = Document::new("doc1".to_string());
+        let mut doc2 = Document::new("doc2".to_string());
+        doc2.set_field("new_field".to_string(), json!("x"), "client2".to_string());
+
+        let log = doc1.merge(&doc2);
+        assert_eq!(log.warnings.len(), 1);
+    }
+
+    #[test]
+    fn merge_silent_applies_changes_without_building_a_log() {
+        let mut doc1 = Document::new("doc1".to_string());
+        let mut doc2 = Document::new("doc2".to_string());
+        doc1.set_field("a".to_string(), json!("old"), "client1".to_string());
+        doc2.set_field("a".to_string(), json!("new"), "client2".to_string());
+
+        doc1.merge_silent(&doc2);
+        assert_eq!(doc1.get_field("a"), Some(json!("new")));
+    }
+
+    #[test]
+    fn history_records_overwritten_lww_values_up_to_depth() {
+        let mut doc = Document::new("doc".to_string()).with_history_depth(2);
+        doc.set_field("a".to_string(), json!(1), "client1".to_string());
+        doc.set_field("a".to_string(), json!(2), "client1".to_string());
+        doc.set_field("a".to_string(), json!(3), "client1".to_string());
+
+        let history: Vec<Value> = doc.history_for("a").map(|entry| entry.value.clone()).collect();
+        assert_eq!(history, vec![json!(1), json!(2)]);
+    }
+
+    #[test]
+    fn add_to_set_records_displaced_value_to_history() {
+        let mut doc = Document::new("doc".to_string()).with_history_depth(1);
+        doc.set_field("a".to_string(), json!("lww value"), "client1".to_string());
+        doc.add_to_set("a".to_string(), json!("tag"), "client1".to_string());
+
+        let history: Vec<Value> = doc.history_for("a").map(|entry| entry.value.clone()).collect();
+        assert_eq!(history, vec![json!("lww value")]);
+        assert_eq!(doc.get_field("a"), Some(Value::Array(vec![json!("tag")])));
+    }
+
+    #[test]
+    fn increment_records_displaced_value_to_history() {
+        let mut doc = Document::new("doc".to_string()).with_history_depth(1);
+        doc.set_field("a".to_string(), json!("lww value"), "client1".to_string());
+        doc.increment("a".to_string(), 5, "client1".to_string());
+
+        let history: Vec<Value> = doc.history_for("a").map(|entry| entry.value.clone()).collect();
+        assert_eq!(history, vec![json!("lww value")]);
+        assert_eq!(doc.get_field("a"), Some(json!(5)));
+    }
+
+    #[test]
+    fn history_is_empty_when_disabled() {
+        let mut doc = Document::new("doc".to_string());
+        doc.set_field("a".to_string(), json!(1), "client1".to_string());
+        doc.set_field("a".to_string(), json!(2), "client1".to_string());
+
+        assert_eq!(doc.history_for("a").count(), 0);
+    }
+
+    #[test]
+    fn iter_fields_preserves_insertion_order() {
+        let mut doc = Document::new("doc".to_string());
+        doc.set_field("z".to_string(), json!(1), "client1".to_string());
+        doc.set_field("a".to_string(), json!(2), "client1".to_string());
+        doc.set_field("m".to_string(), json!(3), "client1".to_string());
+
+        let names: Vec<&str> = doc.iter_fields().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["z", "a", "m"]);
+    }
+
+    #[test]
+    fn to_json_preserves_insertion_order() {
+        let mut doc = Document::new("doc".to_string());
+        doc.set_field("z".to_string(), json!(1), "client1".to_string());
+        doc.set_field("a".to_string(), json!(2), "client1".to_string());
+
+        let keys: Vec<String> = doc
+            .to_json()
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+        assert_eq!(keys, vec!["z".to_string(), "a".to_string()]);
+    }
+
+    #[test]
+    fn overwriting_a_field_keeps_its_original_position() {
+        let mut doc = Document::new("doc".to_string());
+        doc.set_field("a".to_string(), json!(1), "client1".to_string());
+        doc.set_field("b".to_string(), json!(2), "client1".to_string());
+        doc.set_field("a".to_string(), json!(3), "client1".to_string());
+
+        let names: Vec<&str> = doc.iter_fields().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["a", "b"]);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn merge_parallel_matches_serial_merge() {
+        let mut doc1 = Document::new("doc1".to_string());
+        let mut doc2 = Document::new("doc2".to_string());
+        for i in 0..200 {
+            doc1.set_field(format!("field{}", i), json!(format!("v1_{}", i)), "client1".to_string());
+        }
+        for i in 0..200 {
+            doc2.set_field(format!("field{}", i), json!(format!("v2_{}", i)), "client2".to_string());
+        }
+
+        let mut via_serial = doc1.clone();
+        via_serial.merge_silent(&doc2);
+
+        let mut via_parallel = doc1.clone();
+        via_parallel.merge_parallel_silent(&doc2);
+
+        for i in 0..200 {
+            let field = format!("field{}", i);
+            assert_eq!(via_serial.get_field(&field), via_parallel.get_field(&field));
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn merge_parallel_falls_back_to_serial_for_small_documents() {
+        let mut doc1 = Document::new("doc1".to_string());
+        let mut doc2 = Document::new("doc2".to_string());
+        doc1.set_field("a".to_string(), json!("old"), "client1".to_string());
+        doc2.set_field("a".to_string(), json!("new"), "client2".to_string());
+
+        let log = doc1.merge_parallel(&doc2);
+        assert_eq!(log.changes.len(), 1);
+        assert_eq!(doc1.get_field("a"), Some(json!("new")));
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn metrics_snapshot_counts_field_writes() {
+        let mut doc = Document::new("doc".to_string());
+        doc.set_field("a".to_string(), json!(1), "client1".to_string());
+        doc.add_to_set("tags".to_string(), json!("x"), "client1".to_string());
+        doc.increment("likes".to_string(), 1, "client1".to_string());
+
+        assert_eq!(doc.metrics_snapshot().fields_set, 3);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn metrics_snapshot_counts_merges_and_conflict_winners_and_losers() {
+        let mut doc1 = Document::new("doc1".to_string());
+        let mut doc2 = Document::new("doc2".to_string());
+        doc1.set_field("a".to_string(), json!("old"), "client1".to_string());
+        doc2.set_field("a".to_string(), json!("new"), "client2".to_string());
+
+        doc1.merge(&doc2);
+
+        let snapshot = doc1.metrics_snapshot();
+        assert_eq!(snapshot.merges_performed, 1);
+        assert_eq!(snapshot.conflicts_resolved["client2"].wins, 1);
+        assert_eq!(snapshot.conflicts_resolved["client1"].losses, 1);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn metrics_snapshot_counts_bytes_serialized() {
+        let mut doc = Document::new("doc".to_string());
+        doc.set_field("a".to_string(), json!("value"), "client1".to_string());
+
+        assert_eq!(doc.metrics_snapshot().bytes_serialized, 0);
+        doc.to_json();
+        assert!(doc.metrics_snapshot().bytes_serialized > 0);
+    }
+}
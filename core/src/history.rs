@@ -0,0 +1,45 @@
+//! Bounded per-field version history, so applications can show conflict
+//! provenance or implement undo/time-travel over a `Document`.
+
+use crate::hlc::Hlc;
+use serde_json::Value;
+use std::collections::VecDeque;
+
+/// A prior version of a field, evicted by a later local write or merge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub value: Value,
+    pub hlc: Hlc,
+    pub client_id: String,
+}
+
+/// A fixed-depth ring of a single field's prior versions, oldest first.
+/// Pushing past `depth` evicts the oldest entry.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryRing {
+    depth: usize,
+    entries: VecDeque<HistoryEntry>,
+}
+
+impl HistoryRing {
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            entries: VecDeque::with_capacity(depth.min(16)),
+        }
+    }
+
+    pub fn push(&mut self, entry: HistoryEntry) {
+        if self.depth == 0 {
+            return;
+        }
+        self.entries.push_back(entry);
+        while self.entries.len() > self.depth {
+            self.entries.pop_front();
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.entries.iter()
+    }
+}
@@ -0,0 +1,7 @@
+pub mod crdt;
+pub mod document;
+pub mod history;
+pub mod hlc;
+pub mod merge_log;
+#[cfg(feature = "metrics")]
+pub mod metrics;
@@ -0,0 +1,38 @@
+//! Audit trail for `Document::merge`: which field changed, what the losing
+//! side looked like, and anomalies worth surfacing to the caller.
+
+use crate::hlc::Hlc;
+use serde_json::Value;
+
+/// The value, HLC, and client of a field write that lost a merge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoserInfo {
+    pub value: Value,
+    pub hlc: Hlc,
+    pub client_id: String,
+}
+
+/// The outcome of merging one field during a `Document::merge` call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldMergeResult {
+    pub field: String,
+    /// The client whose write is reflected in the post-merge value. For an
+    /// in-place CRDT merge (OR-Set, PN-Counter) this is simply the remote
+    /// client contributing to the merge, not a sole "winner".
+    pub winner_client: String,
+    /// `Some` when a losing LWW value was discarded; `None` for CRDT types
+    /// that merge both sides in rather than picking a winner.
+    pub loser: Option<LoserInfo>,
+}
+
+/// Report produced by `Document::merge`: every field whose value was
+/// replaced, adopted from the remote side, or CRDT-merged (a CRDT field
+/// present on both sides is listed even if the merge left it unchanged), plus
+/// warnings for anomalies such as two clients writing the same field at
+/// the exact same HLC, or a field that did not exist locally before the
+/// merge.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MergeLog {
+    pub changes: Vec<FieldMergeResult>,
+    pub warnings: Vec<String>,
+}
@@ -0,0 +1,105 @@
+//! Lightweight operation metrics for observability. Gated behind the
+//! `metrics` feature so the counters, and the cost of updating them,
+//! compile out entirely when the feature is disabled.
+
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Per-client win/loss tally from LWW conflict resolution during merges.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConflictTally {
+    pub wins: u64,
+    pub losses: u64,
+}
+
+/// A point-in-time snapshot of a document's operation metrics, suitable
+/// for scraping into a time-series store.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    pub fields_set: u64,
+    pub merges_performed: u64,
+    pub conflicts_resolved: HashMap<String, ConflictTally>,
+    pub bytes_serialized: u64,
+    pub last_merge_duration: Duration,
+}
+
+/// Running counters for a single `Document`. Updated from `Document`'s
+/// methods and read out via `Document::metrics_snapshot`.
+#[derive(Debug, Clone, Default)]
+pub struct DocumentMetrics {
+    fields_set: u64,
+    merges_performed: u64,
+    conflicts_resolved: HashMap<String, ConflictTally>,
+    // A `Cell` so byte counts can be recorded from `Document::to_json`,
+    // which only takes `&self`.
+    bytes_serialized: Cell<u64>,
+    last_merge_duration: Duration,
+}
+
+impl DocumentMetrics {
+    pub fn record_field_set(&mut self) {
+        self.fields_set += 1;
+    }
+
+    pub fn record_merge(&mut self, duration: Duration) {
+        self.merges_performed += 1;
+        self.last_merge_duration = duration;
+    }
+
+    pub fn record_conflict_winner(&mut self, client_id: &str) {
+        self.conflicts_resolved
+            .entry(client_id.to_string())
+            .or_default()
+            .wins += 1;
+    }
+
+    pub fn record_conflict_loser(&mut self, client_id: &str) {
+        self.conflicts_resolved
+            .entry(client_id.to_string())
+            .or_default()
+            .losses += 1;
+    }
+
+    /// Record bytes serialized. Takes `&self`: serialization happens from
+    /// read-only methods like `Document::to_json`, so this counter uses
+    /// interior mutability rather than requiring `&mut self` everywhere.
+    pub fn record_bytes_serialized(&self, bytes: u64) {
+        self.bytes_serialized.set(self.bytes_serialized.get() + bytes);
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            fields_set: self.fields_set,
+            merges_performed: self.merges_performed,
+            conflicts_resolved: self.conflicts_resolved.clone(),
+            bytes_serialized: self.bytes_serialized.get(),
+            last_merge_duration: self.last_merge_duration,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_reflects_recorded_operations() {
+        let mut metrics = DocumentMetrics::default();
+        metrics.record_field_set();
+        metrics.record_field_set();
+        metrics.record_merge(Duration::from_micros(42));
+        metrics.record_conflict_winner("client1");
+        metrics.record_conflict_loser("client2");
+        metrics.record_bytes_serialized(128);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.fields_set, 2);
+        assert_eq!(snapshot.merges_performed, 1);
+        assert_eq!(snapshot.last_merge_duration, Duration::from_micros(42));
+        assert_eq!(snapshot.conflicts_resolved["client1"].wins, 1);
+        assert_eq!(snapshot.conflicts_resolved["client2"].losses, 1);
+        assert_eq!(snapshot.bytes_serialized, 128);
+    }
+}